@@ -0,0 +1,284 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::GeneralError;
+
+/// Name used to namespace this tool's directory under the OS-standard config/data roots.
+const APP_NAME: &str = "classic-to-indev";
+
+pub const INPUT_FOLDER: &str = "input";
+pub const INPUT_FILE: &str = "level.dat";
+pub const OUTPUT_MODE: u8 = 0;
+pub const OUTPUT_FOLDER: &str = "output";
+pub const OUTPUT_FILE: &str = "localStorage.js";
+pub const OUTPUT_WEBSITE: &str = "https://classic.minecraft.net";
+pub const OUTPUT_DIRECTION: &str = "classic-to-js";
+pub const BATCH: bool = false;
+pub const MAX_DEPTH: u32 = 8;
+/// Valid range for `save-slot`: classic.minecraft.net keeps 8 save slots, numbered from 1.
+pub const SAVE_SLOT_RANGE: std::ops::RangeInclusive<u8> = 1..=8;
+pub const SAVE_SLOT: u8 = 1;
+/// Valid range for `world-index`: classic.minecraft.net numbers a slot's maps from 0.
+pub const WORLD_INDEX_RANGE: std::ops::RangeInclusive<u8> = 0..=9;
+pub const WORLD_INDEX: u8 = 1;
+
+/// The base name (without extension) that auto-detection looks for on disk.
+const CONFIG_STEM: &str = "config";
+/// Environment variables carrying config overrides are namespaced under this prefix,
+/// with `__` denoting descent into a nested table (e.g. `C2I_OUTPUT__OUTPUT_MODE=1`, which
+/// round-trips to `output_settings.output_mode` via [`expand_section_alias`]).
+const ENV_PREFIX: &str = "C2I_";
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub struct Config {
+    pub input_settings: Input,
+    pub output_settings: Output,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub struct Input {
+    pub input_folder: String,
+    pub input_file: String,
+    /// When `true`, recursively convert every matching level under `input_folder` instead
+    /// of just `input_file`, writing each result under a mirrored path in the output folder.
+    #[serde(deserialize_with = "string_or_native")]
+    pub batch: bool,
+    /// How many directory levels to descend into `input_folder` when `batch` is enabled.
+    #[serde(deserialize_with = "string_or_native")]
+    pub max_depth: u32,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub struct Output {
+    #[serde(deserialize_with = "string_or_native")]
+    pub output_mode: u8,
+    pub output_folder: String,
+    pub output_file: String,
+    pub output_website: String,
+    /// Which way to run the pipeline: `"classic-to-js"` (the default) or `"js-to-classic"`.
+    pub direction: String,
+    /// Which classic.minecraft.net save slot to target. Valid range: [`SAVE_SLOT_RANGE`].
+    #[serde(deserialize_with = "string_or_native")]
+    pub save_slot: u8,
+    /// Which map within the save slot to target. Valid range: [`WORLD_INDEX_RANGE`].
+    #[serde(deserialize_with = "string_or_native")]
+    pub world_index: u8,
+    /// Owning user to chown output files to, by name. Unix only; absent means leave as-is.
+    #[serde(default)]
+    pub user: Option<String>,
+    /// Owning group to chown output files to, by name. Unix only; absent means leave as-is.
+    #[serde(default)]
+    pub group: Option<String>,
+    /// Permission bits to chmod output files to, as an octal string (e.g. `"640"`). Unix
+    /// only; absent means leave as-is.
+    #[serde(default)]
+    pub mode: Option<String>,
+}
+
+/// The built-in defaults, lowest-precedence layer, seeded from the constants above. The
+/// input/output folders default to `data_dir` so portable and OS-standard runs both get a
+/// sensible location without a config file having to specify one.
+fn defaults_layer(data_dir: &Path) -> Value {
+    json!({
+        "input-settings": {
+            "input-folder": data_dir.join(INPUT_FOLDER).display().to_string(),
+            "input-file": INPUT_FILE,
+            "batch": BATCH,
+            "max-depth": MAX_DEPTH,
+        },
+        "output-settings": {
+            "output-mode": OUTPUT_MODE,
+            "output-folder": data_dir.join(OUTPUT_FOLDER).display().to_string(),
+            "output-file": OUTPUT_FILE,
+            "output-website": OUTPUT_WEBSITE,
+            "direction": OUTPUT_DIRECTION,
+            "save-slot": SAVE_SLOT,
+            "world-index": WORLD_INDEX,
+        }
+    })
+}
+
+/// Resolves whether to run in portable mode (config and data relative to the current
+/// directory) by checking, in order: a `--portable=true` CLI flag, a `C2I_PORTABLE`
+/// environment variable, then falling back to `false`.
+pub fn resolve_portable(args: &[String]) -> bool {
+    for arg in args {
+        if let Some(flag) = arg.strip_prefix("--portable=") {
+            return flag.parse().unwrap_or(false);
+        }
+    }
+    if let Ok(value) = std::env::var(format!("{ENV_PREFIX}PORTABLE")) {
+        return value.parse().unwrap_or(false);
+    }
+    false
+}
+
+/// Resolves the config directory (where `config.toml`/`.json`/`.yaml` is read from and
+/// seeded into) and the data directory (the default parent for `input`/`output`). In
+/// portable mode both are the current directory; otherwise they follow OS convention, e.g.
+/// `~/.config/classic-to-indev` and `~/.local/share/classic-to-indev` on Linux.
+pub fn resolve_dirs(portable: bool) -> (PathBuf, PathBuf) {
+    if portable {
+        return (PathBuf::from("."), PathBuf::from("."));
+    }
+    let config_dir = dirs::config_dir().map(|d| d.join(APP_NAME)).unwrap_or_else(|| PathBuf::from("."));
+    let data_dir = dirs::data_dir().map(|d| d.join(APP_NAME)).unwrap_or_else(|| PathBuf::from("."));
+    (config_dir, data_dir)
+}
+
+/// Whether a `config.toml`/`.json`/`.yaml` already exists in `dir`.
+pub fn exists(dir: &Path) -> Result<bool, GeneralError> {
+    for stem in ["toml", "json", "yaml"] {
+        if fs::exists(dir.join(format!("{CONFIG_STEM}.{stem}")))? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Folds `overlay` onto `base`: scalars and arrays in `overlay` replace `base` outright,
+/// objects are merged key-by-key so a source only needs to specify what it overrides.
+fn merge(base: Value, overlay: Value) -> Value {
+    match (base, overlay) {
+        (Value::Object(mut base_map), Value::Object(overlay_map)) => {
+            for (key, value) in overlay_map {
+                let merged = match base_map.remove(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => value,
+                };
+                base_map.insert(key, merged);
+            }
+            Value::Object(base_map)
+        }
+        (_, overlay) => overlay,
+    }
+}
+
+/// Looks for `config.toml`, `config.json`, or `config.yaml` (in that order) in `dir` and
+/// parses whichever is found first into a generic value tree. Returns `None` if none exist.
+fn file_layer(dir: &Path) -> Result<Option<Value>, GeneralError> {
+    let toml_path = dir.join(format!("{CONFIG_STEM}.toml"));
+    let json_path = dir.join(format!("{CONFIG_STEM}.json"));
+    let yaml_path = dir.join(format!("{CONFIG_STEM}.yaml"));
+
+    if fs::exists(&toml_path)? {
+        let raw = fs::read_to_string(&toml_path)?;
+        return Ok(Some(toml::from_str(&raw)?));
+    }
+    if fs::exists(&json_path)? {
+        let raw = fs::read_to_string(&json_path)?;
+        return Ok(Some(serde_json::from_str(&raw)?));
+    }
+    if fs::exists(&yaml_path)? {
+        let raw = fs::read_to_string(&yaml_path)?;
+        return Ok(Some(serde_yaml::from_str(&raw)?));
+    }
+    Ok(None)
+}
+
+/// Builds the environment-variable layer: every `C2I_`-prefixed variable is split on `__`
+/// into nested keys (lower-cased, with `_` treated as `-` to match the kebab-case fields),
+/// then its top-level section is expanded via [`expand_section_alias`]. Values are kept as
+/// plain strings (env vars carry no type information); fields that need a number or bool
+/// accept a string form via [`string_or_native`].
+fn env_layer() -> Value {
+    let mut root = json!({});
+    for (key, value) in std::env::vars() {
+        let Some(rest) = key.strip_prefix(ENV_PREFIX) else { continue };
+        let path: Vec<String> = rest
+            .split("__")
+            .map(|segment| segment.to_lowercase().replace('_', "-"))
+            .collect();
+        insert_path(&mut root, &expand_section_alias(path), Value::String(value));
+    }
+    root
+}
+
+/// Builds the CLI layer from `--input-settings.input-folder=value`-style flags, where `.`
+/// descends into a nested table, mirroring the `__` separator used for environment variables.
+/// The top-level section also goes through [`expand_section_alias`], so `--output.mode=1`
+/// works the same as `--output-settings.mode=1`. Values are kept as plain strings, for the
+/// same reason as [`env_layer`].
+fn cli_layer(args: &[String]) -> Value {
+    let mut root = json!({});
+    for arg in args {
+        let Some(flag) = arg.strip_prefix("--") else { continue };
+        let Some((key, value)) = flag.split_once('=') else { continue };
+        let path: Vec<String> = key.split('.').map(|segment| segment.to_string()).collect();
+        insert_path(&mut root, &expand_section_alias(path), Value::String(value.to_string()));
+    }
+    root
+}
+
+/// Expands the short section names the backlog's own examples use (`input`/`output`) to the
+/// actual struct field names (`input-settings`/`output-settings`) so e.g.
+/// `C2I_OUTPUT__OUTPUT_MODE` reaches `output_settings.output_mode` instead of being silently
+/// dropped as an unknown top-level key. Any other (already-correct) section name, or an empty
+/// path, passes through unchanged.
+fn expand_section_alias(mut path: Vec<String>) -> Vec<String> {
+    if let Some(first) = path.first_mut() {
+        match first.as_str() {
+            "input" => *first = "input-settings".to_string(),
+            "output" => *first = "output-settings".to_string(),
+            _ => {}
+        }
+    }
+    path
+}
+
+/// Deserializes a field that's a native JSON type when it comes from a typed source (the
+/// TOML/JSON/YAML config file) but a plain string when it comes from an untyped source (env
+/// vars, CLI flags). Used for the handful of non-`String` fields; `Option<String>` fields
+/// like `mode`/`user`/`group` need no such handling since a string source is already correct.
+fn string_or_native<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de> + std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Either<T> {
+        Native(T),
+        Str(String),
+    }
+
+    match Either::<T>::deserialize(deserializer)? {
+        Either::Native(v) => Ok(v),
+        Either::Str(s) => s.parse::<T>().map_err(serde::de::Error::custom),
+    }
+}
+
+/// Sets `value` at the nested `path` inside `root`, creating intermediate objects as needed.
+fn insert_path(root: &mut Value, path: &[String], value: Value) {
+    if path.is_empty() {
+        return;
+    }
+    let Value::Object(map) = root else { return };
+    if path.len() == 1 {
+        map.insert(path[0].clone(), value);
+        return;
+    }
+    let child = map.entry(path[0].clone()).or_insert_with(|| json!({}));
+    insert_path(child, &path[1..], value);
+}
+
+/// Loads the layered configuration, merging defaults (seeded under `data_dir`) < the config
+/// file found in `config_dir` < environment < CLI arguments in that order, then deserializes
+/// the result into a concrete [`Config`].
+pub fn load(config_dir: &Path, data_dir: &Path, args: &[String]) -> Result<Config, GeneralError> {
+    let mut merged = defaults_layer(data_dir);
+    if let Some(file) = file_layer(config_dir)? {
+        merged = merge(merged, file);
+    }
+    merged = merge(merged, env_layer());
+    merged = merge(merged, cli_layer(args));
+
+    Ok(serde_json::from_value(merged)?)
+}
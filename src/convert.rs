@@ -0,0 +1,49 @@
+use std::fs;
+
+use thiserror::Error;
+
+use crate::GeneralError;
+
+#[derive(Error, Debug)]
+pub enum ConversionError {
+    #[error("Unsupported block id {0}")]
+    UnsupportedBlock(u8),
+
+    #[error("Level is missing required data")]
+    IncompleteLevel,
+}
+
+/// Converts a Classic level into the block/metadata layout used by the classic.minecraft.net
+/// JS client, targeting the given save slot and world index.
+pub fn classic_to_js(classic: mc_classic::Level, save_slot: u8, world_index: u8) -> Result<mc_classic_js::Data, ConversionError> {
+    mc_classic_js::Data::from_classic(classic, save_slot, world_index)
+        .map_err(|_| ConversionError::IncompleteLevel)
+}
+
+/// Inverts [`classic_to_js`]: reconstructs a Classic level from the block/metadata layout
+/// used by the classic.minecraft.net JS client.
+pub fn js_to_classic(js: mc_classic_js::Data) -> Result<mc_classic::Level, ConversionError> {
+    js.into_classic().map_err(|_| ConversionError::IncompleteLevel)
+}
+
+/// Reads a `localStorage.js` export (as produced by [`mc_classic_js::write_local_storage_command`]
+/// or copied from a browser's devtools) back into the serialized `[String; 2]` pair.
+pub fn read_local_storage(path: &str) -> Result<[String; 2], GeneralError> {
+    let raw = fs::read_to_string(path)?;
+    mc_classic_js::parse_local_storage_command(&raw)
+        .map_err(|_| GeneralError::MalformedInput(path.to_string()))
+}
+
+/// Reads a SQLite export (as produced by [`mc_classic_js::write_data`]) back into the
+/// serialized `[String; 2]` pair.
+pub fn read_sqlite(path: &str) -> Result<[String; 2], GeneralError> {
+    mc_classic_js::read_data(path)
+        .map_err(|_| GeneralError::MalformedInput(path.to_string()))
+}
+
+/// Inverts [`mc_classic_js::serialize_data`], parsing the two serialized strings back into
+/// structured [`mc_classic_js::Data`].
+pub fn deserialize_data(serialized: [String; 2]) -> Result<mc_classic_js::Data, GeneralError> {
+    mc_classic_js::deserialize_data(serialized)
+        .map_err(|_| GeneralError::MalformedInput("serialized level data".to_string()))
+}
@@ -1,47 +1,30 @@
 use std::fs::{self, OpenOptions};
 use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::process::exit;
 
-use serde::Deserialize;
 use thiserror::Error;
 use rusqlite::Error;
 
 use mc_classic;
 
+mod config;
 mod convert;
+mod permissions;
 
-const INPUT_FOLDER: &str = "input";
-const INPUT_FILE: &str = "level.dat";
-const OUTPUT_MODE: u8 = 0;
-const OUTPUT_FOLDER: &str = "output";
-const OUTPUT_FILE: &str = "localStorage.js";
-const OUTPUT_WEBSITE: &str = "https://classic.minecraft.net";
-
-#[derive(Deserialize, Debug)]
-struct Config {
-    input_settings: Input,
-    output_settings: Output
-}
-
-#[derive(Deserialize, Debug)]
-struct Input {
-    input_folder: String,
-    input_file: String
-}
-
-#[derive(Deserialize, Debug)]
-struct Output {
-    output_mode: u8,
-    output_folder: String,
-    output_file: String,
-    output_website: String
-}
+use config::{Config, INPUT_FOLDER, INPUT_FILE, OUTPUT_MODE, OUTPUT_FOLDER, OUTPUT_FILE, OUTPUT_WEBSITE, OUTPUT_DIRECTION, BATCH, MAX_DEPTH, SAVE_SLOT, WORLD_INDEX, SAVE_SLOT_RANGE, WORLD_INDEX_RANGE};
 
 #[derive(Error, Debug)]
 pub enum GeneralError {
     #[error("Error Parsing Config")]
     TOMLError(#[from] toml::de::Error),
 
+    #[error("Error Parsing Config")]
+    JSONError(#[from] serde_json::Error),
+
+    #[error("Error Parsing Config")]
+    YAMLError(#[from] serde_yaml::Error),
+
     #[error("File Error")]
     FileError(#[from] std::io::Error),
 
@@ -57,105 +40,297 @@ pub enum GeneralError {
     #[error("Could not find {0}")]
     MissingFile(String),
 
-    #[error("Output mode invalid, expected 0 or 1 but found {0}")]   
-    InvalidMode(u8)    
+    #[error("Output mode invalid, expected 0 or 1 but found {0}")]
+    InvalidMode(u8),
+
+    #[error("Direction invalid, expected \"classic-to-js\" or \"js-to-classic\" but found {0}")]
+    InvalidDirection(String),
+
+    #[error("Malformed input: {0}")]
+    MalformedInput(String),
+
+    #[error("Invalid save parameters: {0}")]
+    InvalidSaveParameters(String),
+
+    #[error("Could not set output ownership/permissions: {0}")]
+    PermissionError(String),
+
+    #[error("{0}")]
+    UnsetInputFile(String)
 }
 
 fn main () {
 
-    if !fs::exists("config.toml").unwrap() {
-        if let Err(e) = build_settings() {throw(e)}
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let portable = config::resolve_portable(&args);
+    let (config_dir, data_dir) = config::resolve_dirs(portable);
+
+    match config::exists(&config_dir) {
+        Ok(false) => if let Err(e) = build_settings(&config_dir, &data_dir) {throw(e)},
+        Ok(true) => {},
+        Err(e) => throw(e)
     }
 
-    let conf = fs::read_to_string("config.toml").unwrap().replace("-", "_");
-    let config: Config = match toml::from_str(&conf) {
+    let config: Config = match config::load(&config_dir, &data_dir, &args) {
         Ok(c) => c,
         Err(e) => {
-            throw(GeneralError::TOMLError(e));
+            throw(e);
             exit(1)
         }
     };
 
     if !fs::exists(&config.input_settings.input_folder).unwrap() {
-        if let Err(e) = fs::create_dir(&config.input_settings.input_folder) {throw(GeneralError::FileError(e))}
+        if let Err(e) = fs::create_dir_all(&config.input_settings.input_folder) {throw(GeneralError::FileError(e))}
     }
     if !fs::exists(&config.output_settings.output_folder).unwrap() {
-        if let Err(e) = fs::create_dir(&config.output_settings.output_folder) {throw(GeneralError::FileError(e))}
+        if let Err(e) = fs::create_dir_all(&config.output_settings.output_folder) {throw(GeneralError::FileError(e))}
     }
 
-    println!("Loading level");
-    if !fs::exists(config.input_settings.input_folder.clone() + "/" + &config.input_settings.input_file).unwrap() {
-        throw(GeneralError::MissingFile(config.input_settings.input_folder.clone() + "/" + &config.input_settings.input_file));
-    }
-    let classic: mc_classic::Level = match mc_classic::read_level(config.input_settings.input_folder.clone() + "/" + &config.input_settings.input_file) {
-        Ok(c) => c,
-        Err(e) => {
-            throw(GeneralError::ClassicError(e));
+    let result = match config.output_settings.direction.as_str() {
+        "classic-to-js" => run_classic_to_js(&config),
+        "js-to-classic" => run_js_to_classic(&config),
+        other => {
+            throw(GeneralError::InvalidDirection(other.to_string()));
             exit(1)
         }
     };
+    if let Err(e) = result {
+        throw(e);
+    }
+
+    println!("Press Enter to Exit");
+    let mut s: String = String::from("");
+    std::io::stdin().read_line(&mut s).expect("");
+    return;
+
+}
+
+fn run_classic_to_js (config: &Config) -> Result<(), GeneralError> {
+    if config.input_settings.batch {
+        return run_batch(config, &["dat", "mine"], None, convert_classic_to_js_file);
+    }
+
+    let input_path = Path::new(&config.input_settings.input_folder).join(&config.input_settings.input_file);
+    let output_dir = Path::new(&config.output_settings.output_folder).to_path_buf();
+    convert_classic_to_js_file(config, &input_path, &output_dir, &config.output_settings.output_file)
+}
+
+fn run_js_to_classic (config: &Config) -> Result<(), GeneralError> {
+    if config.input_settings.batch {
+        return run_batch(config, &["js", "sqlite"], Some(INPUT_FILE), convert_js_to_classic_file);
+    }
+
+    if config.input_settings.input_file == INPUT_FILE {
+        return Err(GeneralError::UnsetInputFile(format!(
+            "direction is \"js-to-classic\" but input-file is still \"{INPUT_FILE}\" (the classic-to-js default); \
+            set input-file to the localStorage/SQLite export you want to convert back"
+        )));
+    }
+
+    let input_path = Path::new(&config.input_settings.input_folder).join(&config.input_settings.input_file);
+    let output_dir = Path::new(&config.output_settings.output_folder).to_path_buf();
+    convert_js_to_classic_file(config, &input_path, &output_dir, INPUT_FILE)
+}
+
+/// Converts a single level from `input_path` and writes the result under `output_dir` as
+/// `output_file` (mode 1) or inside `output_dir` directly (mode 0, SQLite).
+fn convert_classic_to_js_file (config: &Config, input_path: &Path, output_dir: &Path, output_file: &str) -> Result<(), GeneralError> {
+
+    println!("Loading level: {}", input_path.display());
+    if !fs::exists(input_path)? {
+        return Err(GeneralError::MissingFile(input_path.display().to_string()));
+    }
+    let classic: mc_classic::Level = mc_classic::read_level(input_path.display().to_string())?;
+
+    validate_save_params(config)?;
 
     println!("Converting level");
-    let js: mc_classic_js::Data = match convert::classic_to_js(classic, 1, 1) {
-        Ok(c) => c,
-        Err(e) => {
-            throw(GeneralError::ConversionError(e));
-            exit(1)
-        }
-    };
+    let js: mc_classic_js::Data = convert::classic_to_js(classic, config.output_settings.save_slot, config.output_settings.world_index)?;
 
     println!("Serializing level");
     let serialized: [String; 2] = mc_classic_js::serialize_data(js);
 
-    println!("Writing level");
+    if !fs::exists(output_dir)? {
+        fs::create_dir_all(output_dir)?;
+    }
 
+    println!("Writing level");
     match config.output_settings.output_mode {
         0 => {
-             _ = match mc_classic_js::write_data(config.output_settings.output_folder, serialized, config.output_settings.output_website)  {
-                Ok(c) => c,
-                Err(e) => {
-                    throw(GeneralError::WriteError(e));
-                    exit(1)
-                }
-            };
+            mc_classic_js::write_data(output_dir.display().to_string(), serialized, config.output_settings.output_website.clone())?;
+            permissions::apply(output_dir, &config.output_settings)?;
         },
         1 => {
-           _ = mc_classic_js::write_local_storage_command(
-            config.output_settings.output_folder + "/" + &config.output_settings.output_file,
-            serialized)
+            mc_classic_js::write_local_storage_command(output_dir.join(output_file).display().to_string(), serialized);
+            permissions::apply(&output_dir.join(output_file), &config.output_settings)?;
         }
-        _ => {
-            throw(GeneralError::InvalidMode(config.output_settings.output_mode));
-            exit(1);
+        _ => return Err(GeneralError::InvalidMode(config.output_settings.output_mode))
+    }
+
+    Ok(())
+}
+
+/// Converts a single exported level at `input_path` back into a Classic level, writing
+/// `level.dat` (named `output_file`) under `output_dir`.
+fn convert_js_to_classic_file (config: &Config, input_path: &Path, output_dir: &Path, output_file: &str) -> Result<(), GeneralError> {
+
+    println!("Loading exported data: {}", input_path.display());
+    if !fs::exists(input_path)? {
+        return Err(GeneralError::MissingFile(input_path.display().to_string()));
+    }
+    let input_path = input_path.display().to_string();
+
+    let serialized: [String; 2] = match config.output_settings.output_mode {
+        0 => convert::read_sqlite(&input_path)?,
+        1 => convert::read_local_storage(&input_path)?,
+        _ => return Err(GeneralError::InvalidMode(config.output_settings.output_mode))
+    };
+
+    println!("Deserializing level");
+    let js: mc_classic_js::Data = convert::deserialize_data(serialized)?;
+
+    println!("Converting level");
+    let classic: mc_classic::Level = convert::js_to_classic(js)?;
+
+    if !fs::exists(output_dir)? {
+        fs::create_dir_all(output_dir)?;
+    }
+
+    println!("Writing level");
+    let level_path = output_dir.join(output_file);
+    mc_classic::write_level(level_path.display().to_string(), classic)?;
+    permissions::apply(&level_path, &config.output_settings)?;
+
+    Ok(())
+}
+
+/// Checks `save-slot` and `world-index` are within the ranges classic.minecraft.net accepts
+/// before they're threaded into the conversion.
+fn validate_save_params (config: &Config) -> Result<(), GeneralError> {
+    let save_slot = config.output_settings.save_slot;
+    if !SAVE_SLOT_RANGE.contains(&save_slot) {
+        return Err(GeneralError::InvalidSaveParameters(format!(
+            "save-slot {save_slot} outside valid range {}..={}", SAVE_SLOT_RANGE.start(), SAVE_SLOT_RANGE.end()
+        )));
+    }
+    let world_index = config.output_settings.world_index;
+    if !WORLD_INDEX_RANGE.contains(&world_index) {
+        return Err(GeneralError::InvalidSaveParameters(format!(
+            "world-index {world_index} outside valid range {}..={}", WORLD_INDEX_RANGE.start(), WORLD_INDEX_RANGE.end()
+        )));
+    }
+    Ok(())
+}
+
+/// Recursively walks `input_folder` up to `max_depth` directories deep, converting every file
+/// whose extension is in `extensions` with `convert_one`, mirroring each file's subdirectory
+/// structure under `output_folder` and writing its output into one more subfolder named after
+/// the file's stem (e.g. `input/a/world.dat` goes to `output/a/world/`). `fixed_output_file`, when set,
+/// names every conversion's output file the same way regardless of the source's own basename
+/// (used for js-to-classic, where the result must always be named `level.dat`); when `None`,
+/// each output keeps its source file's basename. Failures are collected rather than aborting
+/// the run, and a summary is printed once every match has been attempted.
+fn run_batch (
+    config: &Config,
+    extensions: &[&str],
+    fixed_output_file: Option<&str>,
+    convert_one: fn(&Config, &Path, &Path, &str) -> Result<(), GeneralError>,
+) -> Result<(), GeneralError> {
+    let input_root = Path::new(&config.input_settings.input_folder);
+    let output_root = Path::new(&config.output_settings.output_folder);
+
+    let files = find_level_files(input_root, extensions, config.input_settings.max_depth);
+    println!("Found {} level(s) under {}", files.len(), input_root.display());
+
+    let mut successes: Vec<PathBuf> = Vec::new();
+    let mut failures: Vec<(PathBuf, GeneralError)> = Vec::new();
+
+    for file in files {
+        let relative = file.strip_prefix(input_root).unwrap_or(&file);
+        let stem = relative.file_stem().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        // Each source level gets its own output subfolder (named after its basename), since
+        // mode 0 (SQLite) writes directly into `output_dir` with no filename of its own —
+        // sharing a folder across files in the same input subdirectory would let one
+        // conversion silently overwrite another's.
+        let output_dir = output_root.join(relative.parent().unwrap_or_else(|| Path::new(""))).join(&stem);
+        let output_file = match fixed_output_file {
+            Some(name) => name.to_string(),
+            None => relative.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default(),
+        };
+
+        match convert_one(config, &file, &output_dir, &output_file) {
+            Ok(()) => successes.push(file),
+            Err(e) => failures.push((file, e)),
         }
     }
 
-    println!("Press Enter to Exit");
-    let mut s: String = String::from("");
-    std::io::stdin().read_line(&mut s).expect("");
-    return;
+    println!("Batch complete: {} succeeded, {} failed", successes.len(), failures.len());
+    for (file, error) in &failures {
+        println!("  FAILED {}: {error}", file.display());
+    }
 
+    Ok(())
 }
 
-fn build_settings () -> Result<(),GeneralError>{
+/// Recursively collects every file under `dir` (down to `max_depth` levels) whose extension
+/// matches one of `extensions`, case-insensitively.
+fn find_level_files (dir: &Path, extensions: &[&str], max_depth: u32) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    let Ok(entries) = fs::read_dir(dir) else { return found };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if max_depth > 0 {
+                found.extend(find_level_files(&path, extensions, max_depth - 1));
+            }
+            continue;
+        }
+        let matches = path.extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| extensions.iter().any(|candidate| candidate.eq_ignore_ascii_case(ext)));
+        if matches {
+            found.push(path);
+        }
+    }
+
+    found
+}
+
+fn build_settings (config_dir: &Path, data_dir: &Path) -> Result<(),GeneralError>{
+    fs::create_dir_all(config_dir)?;
+
+    let input_folder = data_dir.join(INPUT_FOLDER).display().to_string();
+    let output_folder = data_dir.join(OUTPUT_FOLDER).display().to_string();
+
     let mut file = OpenOptions::new()
     .append(true)
     .create(true)
-    .open("config.toml").unwrap();
+    .open(config_dir.join("config.toml"))?;
 
     file.write("[input-settings]\n".as_bytes())?;
-    file.write(format!(r#"input-folder = "{INPUT_FOLDER}""#).as_bytes())?;
+    file.write(format!(r#"input-folder = "{input_folder}""#).as_bytes())?;
     file.write("\n".as_bytes())?;
     file.write(format!(r#"input-file = "{INPUT_FILE}""#).as_bytes())?;
+    file.write("\n".as_bytes())?;
+    file.write(format!(r#"batch = {BATCH}"#).as_bytes())?;
+    file.write("\n".as_bytes())?;
+    file.write(format!(r#"max-depth = {MAX_DEPTH}"#).as_bytes())?;
     file.write("\n\n".as_bytes())?;
     file.write("[output-settings]\n".as_bytes())?;
     file.write(format!(r#"output-mode = {OUTPUT_MODE}"#).as_bytes())?;
     file.write("\n".as_bytes())?;
-    file.write(format!(r#"output-folder = "{OUTPUT_FOLDER}""#).as_bytes())?;
+    file.write(format!(r#"output-folder = "{output_folder}""#).as_bytes())?;
     file.write("\n".as_bytes())?;
     file.write(format!(r#"output-file = "{OUTPUT_FILE}""#).as_bytes())?;
     file.write("\n".as_bytes())?;
     file.write(format!(r#"output-website = "{OUTPUT_WEBSITE}""#).as_bytes())?;
+    file.write("\n".as_bytes())?;
+    file.write(format!(r#"direction = "{OUTPUT_DIRECTION}""#).as_bytes())?;
+    file.write("\n".as_bytes())?;
+    file.write(format!(r#"save-slot = {SAVE_SLOT}"#).as_bytes())?;
+    file.write("\n".as_bytes())?;
+    file.write(format!(r#"world-index = {WORLD_INDEX}"#).as_bytes())?;
     return Ok(())
 }
 
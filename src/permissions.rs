@@ -0,0 +1,57 @@
+use std::path::Path;
+
+use crate::config::Output;
+use crate::GeneralError;
+
+/// Applies the optional `user`, `group`, and `mode` settings from `output` to the file or
+/// directory at `path`, once it's been written. A no-op if none of the three are set.
+///
+/// `mode` is written as given for a file. For a directory (mode 0/SQLite output applies `mode`
+/// to `output_dir` itself, since `mc_classic_js::write_data` addresses its files internally),
+/// a plain file-style octal like `"640"` would strip the traversal bit and make the
+/// just-written files unreachable, so an execute bit is OR'd in wherever a read bit is set
+/// (the same rule `chmod -R` and most umasks use), turning e.g. `644` into `755`.
+#[cfg(unix)]
+pub fn apply(path: &Path, output: &Output) -> Result<(), GeneralError> {
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    if let Some(mode) = &output.mode {
+        let mut parsed = u32::from_str_radix(mode, 8)
+            .map_err(|_| GeneralError::PermissionError(format!("mode {mode} is not a valid octal number")))?;
+        if path.is_dir() {
+            parsed |= (parsed & 0o444) >> 2;
+        }
+        fs::set_permissions(path, fs::Permissions::from_mode(parsed))
+            .map_err(|e| GeneralError::PermissionError(e.to_string()))?;
+    }
+
+    if output.user.is_some() || output.group.is_some() {
+        let uid = match &output.user {
+            Some(name) => Some(users::get_user_by_name(name)
+                .ok_or_else(|| GeneralError::PermissionError(format!("unknown user {name}")))?
+                .uid()),
+            None => None,
+        };
+        let gid = match &output.group {
+            Some(name) => Some(users::get_group_by_name(name)
+                .ok_or_else(|| GeneralError::PermissionError(format!("unknown group {name}")))?
+                .gid()),
+            None => None,
+        };
+        nix::unistd::chown(path, uid.map(nix::unistd::Uid::from_raw), gid.map(nix::unistd::Gid::from_raw))
+            .map_err(|e| GeneralError::PermissionError(e.to_string()))?;
+    }
+
+    Ok(())
+}
+
+/// Ownership and permission bits aren't a portable concept, so outside Unix this is a
+/// best-effort no-op: each call prints a warning if the user asked for something we can't apply.
+#[cfg(not(unix))]
+pub fn apply(_path: &Path, output: &Output) -> Result<(), GeneralError> {
+    if output.user.is_some() || output.group.is_some() || output.mode.is_some() {
+        eprintln!("Warning: output ownership/permissions are not supported on this platform, ignoring");
+    }
+    Ok(())
+}